@@ -1,11 +1,28 @@
 use pyo3::prelude::*;
 use pyo3::exceptions::PyRuntimeError;
-use mainline::{Dht, Id};
+use mainline::{Dht, Id, MutableItem};
 use mainline::common::hash_immutable;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use bytes::Bytes;
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+
+/// Default interval between republishes of each mutable record. BEP44 entries
+/// expire after roughly two hours, so this stays well inside that window.
+const DEFAULT_REPUBLISH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A registered mutable service: its signing key, salt, current sequence
+/// number, and the `PeerInfo` last published under them. The republish task
+/// walks these on an interval and re-puts each one with a bumped `seq`.
+struct MutableServiceEntry {
+    signing_key: SigningKey,
+    salt: Option<Vec<u8>>,
+    seq: i64,
+    peer_info: PeerInfo,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass]
@@ -30,6 +47,7 @@ impl PeerInfo {
 struct BTDht {
     dht: Arc<Mutex<Option<Dht>>>,
     services: Arc<Mutex<HashMap<String, PeerInfo>>>,
+    mutable_services: Arc<Mutex<HashMap<String, MutableServiceEntry>>>,
 }
 
 #[pymethods]
@@ -39,6 +57,7 @@ impl BTDht {
         Self {
             dht: Arc::new(Mutex::new(None)),
             services: Arc::new(Mutex::new(HashMap::new())),
+            mutable_services: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -164,6 +183,165 @@ impl BTDht {
         })
     }
 
+    /// Registers a service under a mutable (owner-signed) BEP44 record, so the
+    /// DHT address stays stable across republishes even as `ws_url`/`port`
+    /// change. Returns the hex-encoded ed25519 public key clients use to find
+    /// it. If `secret_key_hex` is given, that 32-byte seed is reused instead
+    /// of generating a fresh keypair (so the address survives a restart).
+    #[pyo3(signature = (service_key, ws_url, port, salt=None, secret_key_hex=None))]
+    fn register_mutable_service<'py>(
+        &self,
+        py: Python<'py>,
+        service_key: String,
+        ws_url: String,
+        port: u16,
+        salt: Option<String>,
+        secret_key_hex: Option<String>,
+    ) -> PyResult<&'py PyAny> {
+        let signing_key = match secret_key_hex {
+            Some(hex_str) => {
+                let bytes = hex::decode(&hex_str)
+                    .map_err(|e| PyRuntimeError::new_err(format!("Invalid secret key hex: {:?}", e)))?;
+                let arr: [u8; 32] = bytes
+                    .try_into()
+                    .map_err(|_| PyRuntimeError::new_err("Secret key must be 32 bytes"))?;
+                SigningKey::from_bytes(&arr)
+            }
+            None => SigningKey::generate(&mut OsRng),
+        };
+        let public_key_hex = hex::encode(signing_key.verifying_key().to_bytes());
+        let peer_id = format!("py-ws-{}", uuid::Uuid::new_v4());
+        let peer_info = PeerInfo { peer_id, ws_url: ws_url.clone(), port };
+
+        let salt_bytes = salt.clone().map(|s| s.into_bytes());
+        let seq = now_seq();
+
+        let dht_arc = self.dht.clone();
+        let mutable_services = self.mutable_services.clone();
+        let service_key_clone = service_key.clone();
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let value = bincode::serialize(&peer_info)
+                .map_err(|e| PyRuntimeError::new_err(format!("Serialization failed: {:?}", e)))?;
+
+            let dht = dht_arc.lock().unwrap();
+            let dht = dht.as_ref().ok_or_else(|| PyRuntimeError::new_err("DHT not started"))?;
+
+            let item = MutableItem::new(signing_key.clone(), Bytes::from(value), seq, salt_bytes.clone());
+            dht.put_mutable(item)
+                .map_err(|e| PyRuntimeError::new_err(format!("Mutable store failed: {:?}", e)))?;
+
+            println!("✅ Registered {} -> {} as mutable record", service_key_clone, ws_url);
+            println!("   Public key: {}", public_key_hex);
+
+            mutable_services.lock().unwrap().insert(
+                service_key_clone,
+                MutableServiceEntry { signing_key, salt: salt_bytes, seq, peer_info },
+            );
+
+            Ok(Python::with_gil(|py| public_key_hex.into_py(py)))
+        })
+    }
+
+    /// Looks up a mutable record by its ed25519 public key (and optional
+    /// salt), returning the `ws_url` from the response with the highest
+    /// sequence number (mainline already rejects responses with an invalid
+    /// signature before they reach us).
+    #[pyo3(signature = (public_key_hex, salt=None))]
+    fn find_mutable<'py>(
+        &self,
+        py: Python<'py>,
+        public_key_hex: String,
+        salt: Option<String>,
+    ) -> PyResult<&'py PyAny> {
+        let dht_arc = self.dht.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let public_key_bytes = hex::decode(&public_key_hex)
+                .map_err(|e| PyRuntimeError::new_err(format!("Invalid public key hex: {:?}", e)))?;
+            let key_arr: [u8; 32] = public_key_bytes
+                .try_into()
+                .map_err(|_| PyRuntimeError::new_err("Public key must be 32 bytes"))?;
+            let salt_bytes = salt.map(|s| s.into_bytes());
+
+            let dht = dht_arc.lock().unwrap();
+            let dht = dht.as_ref().ok_or_else(|| PyRuntimeError::new_err("DHT not started"))?;
+
+            println!("🔍 Searching for mutable record {}...", public_key_hex);
+
+            // `get_mutable` only yields items whose BEP44 signature (computed
+            // over the bencoded salt+seq+v buffer, which we don't have the
+            // pieces to reconstruct here) already validated against the
+            // requested public key, so there's nothing left for us to verify.
+            let responses = dht.get_mutable(&key_arr, salt_bytes.as_deref(), None);
+
+            let mut best: Option<(i64, PeerInfo)> = None;
+            for item in responses {
+                if let Ok(peer_info) = bincode::deserialize::<PeerInfo>(item.value()) {
+                    if best.as_ref().map_or(true, |(seq, _)| item.seq() > *seq) {
+                        best = Some((item.seq(), peer_info));
+                    }
+                }
+            }
+
+            match best {
+                Some((seq, peer_info)) => {
+                    println!("✅ Found mutable record -> {} (seq {})", peer_info.ws_url, seq);
+                    Ok(Python::with_gil(|py| peer_info.ws_url.into_py(py)))
+                }
+                None => {
+                    println!("❌ No valid mutable record found for {}", public_key_hex);
+                    Ok(Python::with_gil(|py| py.None()))
+                }
+            }
+        })
+    }
+
+    /// Spawns a background task that re-puts every registered mutable
+    /// service on `interval_secs` (default one hour), bumping its sequence
+    /// number each time, so long-lived services don't fall out of the DHT
+    /// between the ~2 hour BEP44 expiries.
+    #[pyo3(signature = (interval_secs=None))]
+    fn start_republish<'py>(&self, py: Python<'py>, interval_secs: Option<u64>) -> PyResult<&'py PyAny> {
+        let interval = interval_secs.map(Duration::from_secs).unwrap_or(DEFAULT_REPUBLISH_INTERVAL);
+        let dht_arc = self.dht.clone();
+        let mutable_services = self.mutable_services.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                ticker.tick().await; // first tick fires immediately; skip it, the initial put already ran
+                loop {
+                    ticker.tick().await;
+                    let dht = dht_arc.lock().unwrap();
+                    let Some(dht) = dht.as_ref() else { continue };
+
+                    let mut services = mutable_services.lock().unwrap();
+                    for (service_key, entry) in services.iter_mut() {
+                        entry.seq += 1;
+                        let value = match bincode::serialize(&entry.peer_info) {
+                            Ok(v) => v,
+                            Err(e) => {
+                                eprintln!("⚠️  Failed to serialize {} for republish: {:?}", service_key, e);
+                                continue;
+                            }
+                        };
+                        let item = MutableItem::new(
+                            entry.signing_key.clone(),
+                            Bytes::from(value),
+                            entry.seq,
+                            entry.salt.clone(),
+                        );
+                        match dht.put_mutable(item) {
+                            Ok(_) => println!("🔁 Republished {} (seq {})", service_key, entry.seq),
+                            Err(e) => eprintln!("⚠️  Republish of {} failed: {:?}", service_key, e),
+                        }
+                    }
+                }
+            });
+            Ok(())
+        })
+    }
+
     fn list_services(&self) -> Vec<(String, String)> {
         self.services.lock().unwrap()
             .iter()
@@ -172,6 +350,15 @@ impl BTDht {
     }
 }
 
+/// BEP44 sequence numbers must strictly increase across puts; seeding from
+/// the current unix timestamp keeps that true even across process restarts.
+fn now_seq() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock before unix epoch")
+        .as_secs() as i64
+}
+
 #[pymodule]
 fn btdht_rs(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<BTDht>()?;
@@ -1,19 +1,33 @@
 // P2P Node: Simple send/listen API for bidirectional byte-stream messaging
 // Transport-agnostic: handles raw bytes, Python handles JSON encoding/decoding
 
+mod nat;
+mod pairing;
+mod peering;
+mod protocol;
+
 use anyhow::Result;
 use clap::Parser;
 use futures::StreamExt;
 use libp2p::{
-    dcutr, identify, kad, noise, ping, relay,
-    swarm::{NetworkBehaviour, SwarmEvent},
-    PeerId, Swarm,
+    autonat, dcutr, identify, kad, mdns, noise, ping, relay, request_response,
+    swarm::{behaviour::toggle::Toggle, NetworkBehaviour, SwarmEvent},
+    Multiaddr, PeerId, Swarm,
+};
+use ed25519_dalek::SigningKey;
+use nat::{NatState, NatStatus, RELAY_HOP_PROTOCOL};
+use pairing::{
+    NodeInformation, PairingCodec, PairingRequest, PairingResponse, TrustStore,
+    TrustedPeer, PROTOCOL_NAME as PAIRING_PROTOCOL_NAME,
 };
+use peering::PeeringSupervisor;
+use protocol::{MsgCodec, MsgRequest, MsgResponse, ReqIdCounter, PROTOCOL_NAME};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
 use tracing::{error, info, warn};
 
 #[derive(Parser, Debug)]
@@ -31,6 +45,35 @@ struct Args {
     /// Port for stdin/stdout interface
     #[arg(long, default_value = "0")]
     port: u16,
+
+    /// Which discovery paths to use: the global Kademlia DHT, LAN mDNS, or both
+    #[arg(long, value_enum, default_value_t = Discovery::Both)]
+    discovery: Discovery,
+
+    /// Directory for persisted pairing state (group key, trusted peers)
+    #[arg(long, default_value = "./p2p-node-data")]
+    data_dir: PathBuf,
+
+    /// Display name advertised to peers during pairing
+    #[arg(long)]
+    display_name: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Discovery {
+    Dht,
+    Mdns,
+    Both,
+}
+
+impl Discovery {
+    fn dht_enabled(self) -> bool {
+        matches!(self, Discovery::Dht | Discovery::Both)
+    }
+
+    fn mdns_enabled(self) -> bool {
+        matches!(self, Discovery::Mdns | Discovery::Both)
+    }
 }
 
 // Network behaviour
@@ -41,12 +84,29 @@ struct P2PBehaviour {
     identify: identify::Behaviour,
     kad: kad::Behaviour<kad::store::MemoryStore>,
     dcutr: dcutr::Behaviour,
+    msg: request_response::Behaviour<MsgCodec>,
+    autonat: autonat::Behaviour,
+    mdns: Toggle<mdns::async_io::Behaviour>,
+    pairing: request_response::Behaviour<PairingCodec>,
 }
 
 // Shared state - transport layer only handles raw bytes
 struct NodeState {
     peers: Arc<RwLock<HashMap<PeerId, mpsc::Sender<Vec<u8>>>>>,
     _service_key: String,
+    req_ids: ReqIdCounter,
+    pending: Mutex<HashMap<u16, oneshot::Sender<Vec<u8>>>>,
+    nat: Mutex<NatState>,
+    peering: Mutex<PeeringSupervisor>,
+    group_key: Mutex<SigningKey>,
+    group_key_path: PathBuf,
+    display_name: String,
+    trust: Mutex<TrustStore>,
+    trust_path: PathBuf,
+    pairing_outgoing: Mutex<HashMap<PeerId, oneshot::Sender<PairingResponse>>>,
+    pairing_incoming: Mutex<
+        HashMap<u32, (PeerId, request_response::ResponseChannel<PairingResponse>, NodeInformation, Option<[u8; 32]>)>,
+    >,
 }
 
 #[tokio::main]
@@ -64,6 +124,8 @@ async fn main() -> Result<()> {
 
     info!("🚀 Starting P2P Node in {} mode", args.mode);
     info!("📡 Service: {}", args.service);
+    info!("🔎 Discovery: {:?}", args.discovery);
+    let discovery = args.discovery;
 
     // Create keypair
     let local_key = libp2p::identity::Keypair::generate_ed25519();
@@ -99,6 +161,23 @@ async fn main() -> Result<()> {
                 )),
                 kad,
                 dcutr: dcutr::Behaviour::new(peer_id),
+                msg: request_response::Behaviour::new(
+                    MsgCodec,
+                    std::iter::once((PROTOCOL_NAME, request_response::ProtocolSupport::Full)),
+                    request_response::Config::default(),
+                ),
+                autonat: autonat::Behaviour::new(peer_id, autonat::Config::default()),
+                mdns: discovery.mdns_enabled().then(|| {
+                    mdns::async_io::Behaviour::new(mdns::Config::default(), peer_id)
+                        .expect("failed to create mdns behaviour")
+                }).into(),
+                // Pairing needs a human to type `accept <code>`, so give it a
+                // much longer request timeout than the default.
+                pairing: request_response::Behaviour::new(
+                    PairingCodec,
+                    std::iter::once((PAIRING_PROTOCOL_NAME, request_response::ProtocolSupport::Full)),
+                    request_response::Config::default().with_request_timeout(Duration::from_secs(300)),
+                ),
             }
         })?
         .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(60)))
@@ -108,47 +187,69 @@ async fn main() -> Result<()> {
     swarm.listen_on("/ip4/0.0.0.0/tcp/0".parse()?)?;
     swarm.listen_on("/ip4/0.0.0.0/udp/0/quic-v1".parse()?)?;
 
-    // Add IPFS bootstrap nodes to join the global DHT
-    info!("🌐 Adding bootstrap nodes...");
-    let bootstrap_nodes = vec![
-        "/dnsaddr/bootstrap.libp2p.io/p2p/QmNnooDu7bfjPFoTZYxMNLWUQJyrVwtbZg5gBMjTezGAJN",
-        "/dnsaddr/bootstrap.libp2p.io/p2p/QmQCU2EcMqAqQPR2i9bChDtGNJchTbq5TbXJJ16u19uLTa",
-        "/dnsaddr/bootstrap.libp2p.io/p2p/QmbLHAnMoJPWSCR5Zhtx6BHJX9KiKNN6tpvbUcqanj75Nb",
-        "/dnsaddr/bootstrap.libp2p.io/p2p/QmcZf59bWwK5XFi76CZX8cbJ4BhTzzA3gU1ZjYZcYW3dwt",
-    ];
-
-    for addr in bootstrap_nodes {
-        if let Ok(multiaddr) = addr.parse::<libp2p::Multiaddr>() {
-            if let Some(libp2p::multiaddr::Protocol::P2p(peer_id)) =
-                multiaddr.iter().find(|p| matches!(p, libp2p::multiaddr::Protocol::P2p(_)))
-            {
-                let peer_id = PeerId::from_multihash(peer_id.into()).expect("valid peer id");
-                swarm.behaviour_mut().kad.add_address(&peer_id, multiaddr);
-                info!("Added bootstrap peer: {}", peer_id);
+    if discovery.dht_enabled() {
+        // Add IPFS bootstrap nodes to join the global DHT
+        info!("🌐 Adding bootstrap nodes...");
+        let bootstrap_nodes = vec![
+            "/dnsaddr/bootstrap.libp2p.io/p2p/QmNnooDu7bfjPFoTZYxMNLWUQJyrVwtbZg5gBMjTezGAJN",
+            "/dnsaddr/bootstrap.libp2p.io/p2p/QmQCU2EcMqAqQPR2i9bChDtGNJchTbq5TbXJJ16u19uLTa",
+            "/dnsaddr/bootstrap.libp2p.io/p2p/QmbLHAnMoJPWSCR5Zhtx6BHJX9KiKNN6tpvbUcqanj75Nb",
+            "/dnsaddr/bootstrap.libp2p.io/p2p/QmcZf59bWwK5XFi76CZX8cbJ4BhTzzA3gU1ZjYZcYW3dwt",
+        ];
+
+        for addr in bootstrap_nodes {
+            if let Ok(multiaddr) = addr.parse::<libp2p::Multiaddr>() {
+                if let Some(libp2p::multiaddr::Protocol::P2p(peer_id)) =
+                    multiaddr.iter().find(|p| matches!(p, libp2p::multiaddr::Protocol::P2p(_)))
+                {
+                    let peer_id = PeerId::from_multihash(peer_id.into()).expect("valid peer id");
+                    swarm.behaviour_mut().kad.add_address(&peer_id, multiaddr);
+                    info!("Added bootstrap peer: {}", peer_id);
+                }
             }
         }
-    }
 
-    // Bootstrap to DHT
-    info!("🌐 Bootstrapping to DHT...");
-    if let Err(e) = swarm.behaviour_mut().kad.bootstrap() {
-        warn!("Kademlia bootstrap failed: {}", e);
+        // Bootstrap to DHT
+        info!("🌐 Bootstrapping to DHT...");
+        if let Err(e) = swarm.behaviour_mut().kad.bootstrap() {
+            warn!("Kademlia bootstrap failed: {}", e);
+        }
+    } else {
+        info!("🌐 DHT discovery disabled, skipping bootstrap");
     }
 
+    let group_key_path = args.data_dir.join("group_key.bin");
+    let group_key = pairing::load_or_generate_group_key(&group_key_path)?;
+    let trust_path = args.data_dir.join("trusted_peers.json");
+    let trust = TrustStore::load(&trust_path);
+    let display_name = args.display_name.clone().unwrap_or_else(|| local_peer_id.to_string());
+
     let state = Arc::new(NodeState {
         peers: Arc::new(RwLock::new(HashMap::new())),
         _service_key: args.service.clone(),
+        req_ids: ReqIdCounter::default(),
+        pending: Mutex::new(HashMap::new()),
+        nat: Mutex::new(NatState::default()),
+        peering: Mutex::new(PeeringSupervisor::default()),
+        group_key: Mutex::new(group_key),
+        group_key_path,
+        display_name,
+        trust: Mutex::new(trust),
+        trust_path,
+        pairing_outgoing: Mutex::new(HashMap::new()),
+        pairing_incoming: Mutex::new(HashMap::new()),
     });
 
     // Handle mode-specific setup
     let service_key = service_key(&args.service);
     let mut registered = false;
-    let mut discovered_peer: Option<PeerId> = None;
     let mut bootstrapped = false;
 
-    // For client: periodically retry get_providers until we find a server.
+    // For client: periodically retry get_providers to keep discovering peers
+    // for the mesh (the peering supervisor below handles staying connected
+    // to ones we've already found).
     let (lookup_tx, mut lookup_rx) = mpsc::channel::<()>(1);
-    if args.mode == "client" {
+    if args.mode == "client" && discovery.dht_enabled() {
         let lookup_tx_clone = lookup_tx.clone();
         tokio::spawn(async move {
             // initial delay to give server time to come up and announce
@@ -162,6 +263,9 @@ async fn main() -> Result<()> {
         });
     }
 
+    // Ticks the peering supervisor to pick up peers whose backoff has elapsed.
+    let mut redial_interval = tokio::time::interval(Duration::from_millis(500));
+
     // Channel for stdin commands
     let (stdin_tx, mut stdin_rx) = mpsc::channel::<String>(100);
 
@@ -181,6 +285,11 @@ async fn main() -> Result<()> {
     info!("✅ Ready! Commands:");
     info!("   send <peer_id> <message>  - Send message to peer");
     info!("   list                      - List connected peers");
+    info!("   nat                       - Show NAT status and active relay");
+    info!("   peers                     - Show known peers and connection state");
+    info!("   pair <peer_id>            - Start pairing with a peer");
+    info!("   accept <code>             - Accept an incoming pairing request");
+    info!("   trusted                   - List trusted (paired) peers");
 
     // Main event loop
     loop {
@@ -195,8 +304,72 @@ async fn main() -> Result<()> {
                         identify::Event::Received { peer_id, info },
                     )) => {
                         info!("🔍 Identified peer {}", peer_id);
+                        let is_relay = info
+                            .protocols
+                            .iter()
+                            .any(|p| p.as_ref() == RELAY_HOP_PROTOCOL);
                         for addr in info.listen_addrs {
+                            swarm.behaviour_mut().kad.add_address(&peer_id, addr.clone());
+                            if is_relay {
+                                state.nat.lock().await.note_relay_candidate(peer_id, addr);
+                            }
+                        }
+                    }
+
+                    SwarmEvent::Behaviour(P2PBehaviourEvent::Mdns(mdns::Event::Discovered(found))) => {
+                        for (peer_id, addr) in found {
+                            info!("📡 mDNS discovered {} at {}", peer_id, addr);
                             swarm.behaviour_mut().kad.add_address(&peer_id, addr);
+
+                            let mut peering = state.peering.lock().await;
+                            let is_new = peering.snapshot().iter().all(|(id, _)| id != &peer_id);
+                            peering.track(peer_id);
+                            drop(peering);
+
+                            if is_new {
+                                if let Err(e) = swarm.dial(peer_id) {
+                                    error!("Failed to dial mDNS peer {}: {}", peer_id, e);
+                                    state.peering.lock().await.mark_dial_failed(&peer_id);
+                                }
+                            }
+                        }
+                    }
+
+                    SwarmEvent::Behaviour(P2PBehaviourEvent::Autonat(
+                        autonat::Event::StatusChanged { old, new },
+                    )) => {
+                        info!("🌐 AutoNAT status changed: {:?} -> {:?}", old, new);
+                        let mut nat = state.nat.lock().await;
+                        nat.status = match new {
+                            autonat::NatStatus::Public(_) => NatStatus::Public,
+                            autonat::NatStatus::Private => NatStatus::Private,
+                            autonat::NatStatus::Unknown => NatStatus::Unknown,
+                        };
+
+                        if nat.status == NatStatus::Private && nat.active_relay.is_none() {
+                            if let Some((relay_peer, relay_addr)) = nat.pick_relay() {
+                                drop(nat);
+                                info!("🔁 Requesting relay reservation via {}", relay_peer);
+                                if let Err(e) = swarm.dial(relay_addr.clone()) {
+                                    warn!("Failed to dial relay {}: {}", relay_peer, e);
+                                } else {
+                                    let circuit_addr = relay_addr
+                                        .with(libp2p::multiaddr::Protocol::P2p(relay_peer.into()))
+                                        .with(libp2p::multiaddr::Protocol::P2pCircuit);
+                                    match swarm.listen_on(circuit_addr.clone()) {
+                                        Ok(_) => {
+                                            state.nat.lock().await.active_relay = Some(relay_peer);
+                                            swarm
+                                                .behaviour_mut()
+                                                .kad
+                                                .add_address(&relay_peer, circuit_addr);
+                                        }
+                                        Err(e) => warn!("Failed to listen on relay circuit: {}", e),
+                                    }
+                                }
+                            } else {
+                                warn!("Private NAT detected but no relay candidates known yet");
+                            }
                         }
                     }
 
@@ -221,11 +394,19 @@ async fn main() -> Result<()> {
                             providers, ..
                         })) => {
                             if !providers.is_empty() {
-                                let peer_id = *providers.iter().next().unwrap();
-                                info!("✅ Found service provider: {}", peer_id);
-                                discovered_peer = Some(peer_id);
-                                if let Err(e) = swarm.dial(peer_id) {
-                                    error!("Failed to dial: {}", e);
+                                for peer_id in providers {
+                                    let mut peering = state.peering.lock().await;
+                                    let is_new = peering.snapshot().iter().all(|(id, _)| id != &peer_id);
+                                    peering.track(peer_id);
+                                    drop(peering);
+
+                                    if is_new {
+                                        info!("✅ Found service provider: {}", peer_id);
+                                        if let Err(e) = swarm.dial(peer_id) {
+                                            error!("Failed to dial: {}", e);
+                                            state.peering.lock().await.mark_dial_failed(&peer_id);
+                                        }
+                                    }
                                 }
                             } else {
                                 warn!("No providers returned in FoundProviders");
@@ -238,8 +419,120 @@ async fn main() -> Result<()> {
                         _ => {}
                     },
 
-                    SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                    SwarmEvent::Behaviour(P2PBehaviourEvent::Msg(request_response::Event::Message {
+                        peer,
+                        message,
+                        ..
+                    })) => match message {
+                        request_response::Message::Request { request, channel, .. } => {
+                            let our_group_key = state.group_key.lock().await.verifying_key().to_bytes();
+                            if !state.trust.lock().await.is_trusted(&peer.to_string(), &our_group_key) {
+                                warn!("🚫 Rejecting message from unpaired peer {}", peer);
+                                drop(channel);
+                                continue;
+                            }
+                            info!("📥 Inbound message from {} ({} bytes)", peer, request.payload.len());
+                            if let Some(sender) = state.peers.read().await.get(&peer) {
+                                if sender.send(request.payload).await.is_err() {
+                                    warn!("Receiver for {} dropped", peer);
+                                }
+                            }
+                            let response = MsgResponse { req_id: request.req_id, payload: b"ack".to_vec() };
+                            if swarm.behaviour_mut().msg.send_response(channel, response).is_err() {
+                                warn!("Failed to send response to {}", peer);
+                            }
+                        }
+                        request_response::Message::Response { response, .. } => {
+                            if let Some(tx) = state.pending.lock().await.remove(&response.req_id) {
+                                let _ = tx.send(response.payload);
+                            }
+                        }
+                    },
+
+                    SwarmEvent::Behaviour(P2PBehaviourEvent::Msg(request_response::Event::OutboundFailure {
+                        peer,
+                        error,
+                        ..
+                    })) => {
+                        error!("Outbound message to {} failed: {}", peer, error);
+                    }
+
+                    SwarmEvent::Behaviour(P2PBehaviourEvent::Msg(request_response::Event::InboundFailure {
+                        peer,
+                        error,
+                        ..
+                    })) => {
+                        error!("Inbound message from {} failed: {}", peer, error);
+                    }
+
+                    SwarmEvent::Behaviour(P2PBehaviourEvent::Pairing(request_response::Event::Message {
+                        peer,
+                        message,
+                        ..
+                    })) => match message {
+                        request_response::Message::Request { request, channel, .. } => {
+                            info!(
+                                "👋 Pairing request from {} ({}) — code: {}",
+                                peer, request.info.display_name, request.code
+                            );
+                            println!("Run `accept {}` to confirm pairing with {}", request.code, peer);
+                            state.pairing_incoming.lock().await.insert(
+                                request.code,
+                                (peer, channel, request.info, request.group_secret_key),
+                            );
+                        }
+                        request_response::Message::Response { response, .. } => {
+                            if let Some(tx) = state.pairing_outgoing.lock().await.remove(&peer) {
+                                let _ = tx.send(response);
+                            }
+                        }
+                    },
+
+                    SwarmEvent::Behaviour(P2PBehaviourEvent::Pairing(request_response::Event::OutboundFailure {
+                        peer,
+                        error,
+                        ..
+                    })) => {
+                        error!("Pairing request to {} failed: {}", peer, error);
+                        state.pairing_outgoing.lock().await.remove(&peer);
+                    }
+
+                    SwarmEvent::Behaviour(P2PBehaviourEvent::Pairing(request_response::Event::InboundFailure {
+                        peer,
+                        error,
+                        ..
+                    })) => {
+                        error!("Pairing request from {} failed: {}", peer, error);
+                    }
+
+                    SwarmEvent::OutgoingConnectionError { peer_id: Some(peer_id), error, .. } => {
+                        warn!("Dial to {} failed: {}", peer_id, error);
+                        state.peering.lock().await.mark_dial_failed(&peer_id);
+                    }
+
+                    SwarmEvent::ConnectionEstablished { peer_id, num_established, .. } => {
                         info!("🔗 Connected to {}", peer_id);
+                        state.peering.lock().await.mark_connected(peer_id);
+                        let our_group_key = state.group_key.lock().await.verifying_key().to_bytes();
+                        let is_trusted = state.trust.lock().await.is_trusted(&peer_id.to_string(), &our_group_key);
+                        if num_established.get() == 1 && is_trusted {
+                            let (tx, mut rx) = mpsc::channel::<Vec<u8>>(32);
+                            state.peers.write().await.insert(peer_id, tx);
+                            tokio::spawn(async move {
+                                while let Some(payload) = rx.recv().await {
+                                    match String::from_utf8(payload.clone()) {
+                                        Ok(text) => println!("[{peer_id}] {text}"),
+                                        Err(_) => println!("[{peer_id}] <{} bytes>", payload.len()),
+                                    }
+                                }
+                            });
+                        }
+                    }
+
+                    SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                        info!("🔌 Disconnected from {}", peer_id);
+                        state.peers.write().await.remove(&peer_id);
+                        state.peering.lock().await.mark_disconnected(&peer_id);
                     }
 
                     _ => {}
@@ -247,11 +540,23 @@ async fn main() -> Result<()> {
             }
 
             // periodic provider lookup in client mode
-            Some(_) = lookup_rx.recv(), if args.mode == "client" && discovered_peer.is_none() && bootstrapped => {
+            Some(_) = lookup_rx.recv(), if args.mode == "client" && bootstrapped => {
                 info!("🔍 Looking up service providers for '{}'", args.service);
                 swarm.behaviour_mut().kad.get_providers(service_key.clone());
             }
 
+            // redial peers whose backoff has elapsed
+            _ = redial_interval.tick() => {
+                let due = state.peering.lock().await.due_for_redial();
+                for peer_id in due {
+                    info!("🔁 Redialing {}", peer_id);
+                    if let Err(e) = swarm.dial(peer_id) {
+                        warn!("Redial of {} failed: {}", peer_id, e);
+                        state.peering.lock().await.mark_dial_failed(&peer_id);
+                    }
+                }
+            }
+
             Some(cmd) = stdin_rx.recv() => {
                 handle_command(&cmd, &mut swarm, &state, &local_peer_id).await;
             }
@@ -261,9 +566,9 @@ async fn main() -> Result<()> {
 
 async fn handle_command(
     cmd: &str,
-    _swarm: &mut Swarm<P2PBehaviour>,
+    swarm: &mut Swarm<P2PBehaviour>,
     state: &Arc<NodeState>,
-    _local_peer_id: &PeerId,
+    local_peer_id: &PeerId,
 ) {
     let parts: Vec<&str> = cmd.trim().split_whitespace().collect();
 
@@ -272,8 +577,36 @@ async fn handle_command(
             let peer_str = parts[1];
             let message = parts[2..].join(" ");
 
-            // For demo, just print - actual stream handling would go here
-            info!("📤 Would send to {}: {}", peer_str, message);
+            let peer_id: PeerId = match peer_str.parse() {
+                Ok(id) => id,
+                Err(e) => {
+                    error!("Invalid peer id {}: {}", peer_str, e);
+                    return;
+                }
+            };
+
+            let req_id = state.req_ids.next();
+            let (tx, rx) = oneshot::channel();
+            state.pending.lock().await.insert(req_id, tx);
+
+            let request = MsgRequest { req_id, payload: message.into_bytes() };
+            swarm.behaviour_mut().msg.send_request(&peer_id, request);
+            info!("📤 Sent to {} (req_id {})", peer_id, req_id);
+
+            // Wait for the ack off the event loop: the loop is what actually
+            // drives the swarm and resolves `rx` via the Msg Response handler,
+            // so awaiting it inline here would deadlock the whole node.
+            let state = Arc::clone(state);
+            tokio::spawn(async move {
+                match tokio::time::timeout(Duration::from_secs(10), rx).await {
+                    Ok(Ok(ack)) => info!("✅ Ack from {} ({} bytes)", peer_id, ack.len()),
+                    Ok(Err(_)) => warn!("Response channel for req_id {} dropped", req_id),
+                    Err(_) => {
+                        state.pending.lock().await.remove(&req_id);
+                        warn!("Timed out waiting for ack from {}", peer_id);
+                    }
+                }
+            });
         }
         Some("list") => {
             let peers = state.peers.read().await;
@@ -282,8 +615,150 @@ async fn handle_command(
                 println!("{}", peer);
             }
         }
+        Some("nat") => {
+            let nat = state.nat.lock().await;
+            println!("NAT status: {}", nat.describe());
+        }
+        Some("peers") => {
+            let snapshot = state.peering.lock().await.snapshot();
+            info!("📋 Known peers: {}", snapshot.len());
+            for (peer_id, peer_state) in snapshot {
+                println!("{} - {}", peer_id, peer_state);
+            }
+        }
+        Some("pair") if parts.len() == 2 => {
+            let peer_id: PeerId = match parts[1].parse() {
+                Ok(id) => id,
+                Err(e) => {
+                    error!("Invalid peer id {}: {}", parts[1], e);
+                    return;
+                }
+            };
+
+            let group_key = state.group_key.lock().await.clone();
+
+            let info = NodeInformation {
+                peer_id: local_peer_id.to_string(),
+                display_name: state.display_name.clone(),
+                group_public_key: group_key.verifying_key().to_bytes(),
+            };
+            let code = pairing::generate_code();
+            println!("🔑 Pairing code: {code} — confirm it matches on {peer_id}, then ask them to run `accept {code}`");
+
+            let (tx, rx) = oneshot::channel();
+            state.pairing_outgoing.lock().await.insert(peer_id, tx);
+
+            let request = PairingRequest {
+                code,
+                info,
+                // Always sent, so a responder not yet in a group adopts our
+                // group signing key; one already in a different group keeps
+                // its own.
+                group_secret_key: Some(group_key.to_bytes()),
+            };
+            swarm.behaviour_mut().pairing.send_request(&peer_id, request);
+
+            // Wait for the acceptance off the event loop: the loop is what
+            // drives the swarm and resolves `rx` via the Pairing Response
+            // handler, so awaiting it inline here would deadlock the whole
+            // node for up to 120s.
+            let state = Arc::clone(state);
+            tokio::spawn(async move {
+                match tokio::time::timeout(Duration::from_secs(120), rx).await {
+                    Ok(Ok(response)) if response.accepted => {
+                        if let Some(their_info) = response.info {
+                            state.trust.lock().await.insert(
+                                their_info.peer_id.clone(),
+                                TrustedPeer {
+                                    display_name: their_info.display_name,
+                                    group_public_key: their_info.group_public_key,
+                                },
+                            );
+                            if let Err(e) = state.trust.lock().await.save(&state.trust_path) {
+                                error!("Failed to persist trust store: {}", e);
+                            }
+                            info!("✅ Paired with {}", peer_id);
+                        }
+                    }
+                    Ok(Ok(_)) => warn!("Pairing with {} was rejected", peer_id),
+                    Ok(Err(_)) => warn!("Pairing response channel for {} dropped", peer_id),
+                    Err(_) => {
+                        state.pairing_outgoing.lock().await.remove(&peer_id);
+                        warn!("Timed out waiting for {} to accept pairing", peer_id);
+                    }
+                }
+            });
+        }
+        Some("accept") if parts.len() == 2 => {
+            let code: u32 = match parts[1].parse() {
+                Ok(c) => c,
+                Err(e) => {
+                    error!("Invalid pairing code {}: {}", parts[1], e);
+                    return;
+                }
+            };
+
+            let Some((peer_id, channel, their_info, group_secret_key)) =
+                state.pairing_incoming.lock().await.remove(&code)
+            else {
+                warn!("No pending pairing request with code {}", code);
+                return;
+            };
+
+            if let Some(secret) = group_secret_key {
+                let incoming_public_key = SigningKey::from_bytes(&secret).verifying_key().to_bytes();
+                let current_public_key = state.group_key.lock().await.verifying_key().to_bytes();
+                let already_in_a_group = state.trust.lock().await.iter().next().is_some();
+
+                if incoming_public_key == current_public_key {
+                    // Already on this group key; nothing to adopt.
+                } else if already_in_a_group {
+                    // We're already an established member of a different group —
+                    // adopting a newcomer's key here would silently orphan our
+                    // existing trust relationships.
+                    warn!("🚫 Ignoring group key offered by {} — we already belong to a group", peer_id);
+                } else {
+                    *state.group_key.lock().await = SigningKey::from_bytes(&secret);
+                    if let Err(e) = std::fs::write(&state.group_key_path, secret) {
+                        error!("Failed to persist adopted group key: {}", e);
+                    }
+                    info!("🔑 Adopted shared group key from {}", peer_id);
+                }
+            }
+
+            state.trust.lock().await.insert(
+                their_info.peer_id.clone(),
+                TrustedPeer {
+                    display_name: their_info.display_name.clone(),
+                    group_public_key: their_info.group_public_key,
+                },
+            );
+            if let Err(e) = state.trust.lock().await.save(&state.trust_path) {
+                error!("Failed to persist trust store: {}", e);
+            }
+
+            let group_key = state.group_key.lock().await.clone();
+            let our_info = NodeInformation {
+                peer_id: local_peer_id.to_string(),
+                display_name: state.display_name.clone(),
+                group_public_key: group_key.verifying_key().to_bytes(),
+            };
+            let response = PairingResponse { accepted: true, info: Some(our_info) };
+            if swarm.behaviour_mut().pairing.send_response(channel, response).is_err() {
+                warn!("Failed to send pairing acceptance to {}", peer_id);
+            } else {
+                info!("✅ Accepted pairing with {} ({})", peer_id, their_info.display_name);
+            }
+        }
+        Some("trusted") => {
+            let trust = state.trust.lock().await;
+            println!("Trusted peers:");
+            for (peer_id, trusted) in trust.iter() {
+                println!("{} - {}", peer_id, trusted.display_name);
+            }
+        }
         _ => {
-            info!("❓ Unknown command. Try: send <peer_id> <msg> | list");
+            info!("❓ Unknown command. Try: send <peer_id> <msg> | list | nat | peers | pair <peer_id> | accept <code> | trusted");
         }
     }
 }
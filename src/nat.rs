@@ -0,0 +1,66 @@
+// NAT status tracking and relay reservation bookkeeping.
+//
+// The node starts out not knowing whether it is publicly reachable. Once
+// AutoNAT settles on `Private`, we pick one of the relay-capable peers we
+// learned about via `identify` and ask it for a `/p2p-circuit` reservation.
+// `dcutr` then takes over upgrading any resulting relayed connection to a
+// direct one, so this module only needs to track state, not do the punching
+// itself.
+
+use libp2p::{Multiaddr, PeerId};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Protocol name relay servers advertise via `identify` for circuit v2 hop support.
+pub const RELAY_HOP_PROTOCOL: &str = "/libp2p/circuit/relay/0.2.0/hop";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NatStatus {
+    #[default]
+    Unknown,
+    Public,
+    Private,
+}
+
+impl fmt::Display for NatStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NatStatus::Unknown => write!(f, "unknown"),
+            NatStatus::Public => write!(f, "public"),
+            NatStatus::Private => write!(f, "private"),
+        }
+    }
+}
+
+/// Tracks NAT reachability and the relay we've reserved through, if any.
+#[derive(Debug, Default)]
+pub struct NatState {
+    pub status: NatStatus,
+    /// Relay-capable peers discovered via identify, with one of their known addresses.
+    relay_candidates: HashMap<PeerId, Multiaddr>,
+    /// The relay we've requested (or obtained) a circuit reservation through.
+    pub active_relay: Option<PeerId>,
+}
+
+impl NatState {
+    pub fn note_relay_candidate(&mut self, peer_id: PeerId, addr: Multiaddr) {
+        self.relay_candidates.entry(peer_id).or_insert(addr);
+    }
+
+    /// Picks a relay candidate we haven't already reserved through.
+    pub fn pick_relay(&self) -> Option<(PeerId, Multiaddr)> {
+        self.relay_candidates
+            .iter()
+            .find(|(peer, _)| Some(**peer) != self.active_relay)
+            .map(|(peer, addr)| (*peer, addr.clone()))
+    }
+
+    pub fn describe(&self) -> String {
+        match (self.status, self.active_relay) {
+            (NatStatus::Public, _) => "public (directly reachable)".to_string(),
+            (NatStatus::Private, Some(relay)) => format!("private, relayed via {relay}"),
+            (NatStatus::Private, None) => "private, no relay reservation yet".to_string(),
+            (NatStatus::Unknown, _) => "unknown (AutoNAT probing)".to_string(),
+        }
+    }
+}
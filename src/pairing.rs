@@ -0,0 +1,181 @@
+// Pairing handshake: establishes a trusted group between nodes over a
+// dedicated stream protocol, inspired by "pairing by library" flows. The
+// initiator sends a short human-verifiable numeric code plus its
+// `NodeInformation`; the responder surfaces the code to its operator via the
+// `accept <code>` command, and on acceptance a responder that has no trusted
+// peers yet adopts the initiator's group keypair, so both sides hold the same
+// one. A responder that already belongs to a different group keeps its own
+// key; the pairing is still recorded, but neither side then counts the other
+// as trusted. Only peers with a matching, persisted group credential are
+// treated as trusted once reconnected.
+
+use async_trait::async_trait;
+use ed25519_dalek::SigningKey;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::request_response;
+use libp2p::StreamProtocol;
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+pub const PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/p2p-simple/pairing/0.1.0");
+
+/// What a node tells a peer about itself during pairing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeInformation {
+    pub peer_id: String,
+    pub display_name: String,
+    pub group_public_key: [u8; 32],
+}
+
+/// Sent by the initiator. `group_secret_key` is always set; a responder adopts
+/// it unless it already belongs to a different group, in which case it keeps
+/// its own group signing key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingRequest {
+    pub code: u32,
+    pub info: NodeInformation,
+    pub group_secret_key: Option<[u8; 32]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingResponse {
+    pub accepted: bool,
+    pub info: Option<NodeInformation>,
+}
+
+/// Generates a 6-digit human-verifiable pairing code.
+pub fn generate_code() -> u32 {
+    rand::thread_rng().gen_range(100_000..1_000_000)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PairingCodec;
+
+async fn read_blob<T>(io: &mut T) -> io::Result<Vec<u8>>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let mut len_buf = [0u8; 4];
+    io.read_exact(&mut len_buf).await?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+    let mut buf = vec![0u8; len];
+    io.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+async fn write_blob<T>(io: &mut T, bytes: &[u8]) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+{
+    io.write_all(&(bytes.len() as u32).to_be_bytes()).await?;
+    io.write_all(bytes).await?;
+    io.flush().await?;
+    Ok(())
+}
+
+#[async_trait]
+impl request_response::Codec for PairingCodec {
+    type Protocol = StreamProtocol;
+    type Request = PairingRequest;
+    type Response = PairingResponse;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let bytes = read_blob(io).await?;
+        bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    async fn read_response<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let bytes = read_blob(io).await?;
+        bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    async fn write_request<T>(&mut self, _: &Self::Protocol, io: &mut T, req: Self::Request) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let bytes = bincode::serialize(&req).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_blob(io, &bytes).await
+    }
+
+    async fn write_response<T>(&mut self, _: &Self::Protocol, io: &mut T, res: Self::Response) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let bytes = bincode::serialize(&res).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_blob(io, &bytes).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedPeer {
+    pub display_name: String,
+    pub group_public_key: [u8; 32],
+}
+
+/// Persisted set of trusted peers, keyed by their base58 peer id.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    peers: HashMap<String, TrustedPeer>,
+}
+
+impl TrustStore {
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+
+    pub fn insert(&mut self, peer_id: String, trusted: TrustedPeer) {
+        self.peers.insert(peer_id, trusted);
+    }
+
+    /// A peer is trusted only if it's in the allowlist *and* the group
+    /// credential we recorded for it at pairing time still matches our own
+    /// current group key — a stale or mismatched credential (e.g. after a
+    /// group key rotation) no longer counts as trusted.
+    pub fn is_trusted(&self, peer_id: &str, our_group_public_key: &[u8; 32]) -> bool {
+        self.peers
+            .get(peer_id)
+            .is_some_and(|trusted| &trusted.group_public_key == our_group_public_key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &TrustedPeer)> {
+        self.peers.iter()
+    }
+}
+
+/// Loads our own group signing key from disk, generating and persisting a
+/// fresh one on first run.
+pub fn load_or_generate_group_key(path: &Path) -> io::Result<SigningKey> {
+    if let Ok(bytes) = std::fs::read(path) {
+        if let Ok(arr) = <[u8; 32]>::try_from(bytes.as_slice()) {
+            return Ok(SigningKey::from_bytes(&arr));
+        }
+    }
+
+    let key = SigningKey::generate(&mut OsRng);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, key.to_bytes())?;
+    Ok(key)
+}
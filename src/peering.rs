@@ -0,0 +1,111 @@
+// Peering supervisor: keeps a target set of known service peers connected,
+// redialing on drop with exponential backoff + jitter (mirrors the
+// start-small, double, cap, reset-on-success shape used by most gossip
+// meshes): ~500ms initial, doubling up to a ~60s cap, reset to the initial
+// delay as soon as a dial succeeds.
+
+use libp2p::PeerId;
+use rand::Rng;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Upper bound on dials the supervisor will have in flight at once.
+pub const MAX_CONCURRENT_DIALS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeerState {
+    Connected,
+    Dialing,
+    BackingOff { next_retry: Instant },
+}
+
+impl std::fmt::Display for PeerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeerState::Connected => write!(f, "connected"),
+            PeerState::Dialing => write!(f, "reconnecting"),
+            PeerState::BackingOff { next_retry } => {
+                let remaining = next_retry.saturating_duration_since(Instant::now());
+                write!(f, "backing-off (retry in {:.1}s)", remaining.as_secs_f32())
+            }
+        }
+    }
+}
+
+struct PeerEntry {
+    state: PeerState,
+    backoff: Duration,
+}
+
+/// Tracks the set of known service peers and when each is next due for a
+/// redial attempt.
+#[derive(Default)]
+pub struct PeeringSupervisor {
+    peers: HashMap<PeerId, PeerEntry>,
+}
+
+impl PeeringSupervisor {
+    /// Adds a newly-discovered peer to the target set if it isn't known yet.
+    pub fn track(&mut self, peer_id: PeerId) {
+        self.peers.entry(peer_id).or_insert_with(|| PeerEntry {
+            state: PeerState::Dialing,
+            backoff: INITIAL_BACKOFF,
+        });
+    }
+
+    pub fn mark_connected(&mut self, peer_id: PeerId) {
+        if let Some(entry) = self.peers.get_mut(&peer_id) {
+            entry.state = PeerState::Connected;
+            entry.backoff = INITIAL_BACKOFF;
+        }
+    }
+
+    /// Called when a connection drops; schedules the next redial with
+    /// jittered exponential backoff.
+    pub fn mark_disconnected(&mut self, peer_id: &PeerId) {
+        if let Some(entry) = self.peers.get_mut(peer_id) {
+            let jitter = rand::thread_rng().gen_range(0.0..0.3);
+            let jittered = entry.backoff.mul_f64(1.0 + jitter);
+            entry.state = PeerState::BackingOff { next_retry: Instant::now() + jittered };
+            entry.backoff = (entry.backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    pub fn mark_dial_failed(&mut self, peer_id: &PeerId) {
+        self.mark_disconnected(peer_id);
+    }
+
+    /// Returns peers whose backoff has elapsed and are ready to redial, up to
+    /// the concurrent-dial cap. A peer counts against the cap for as long as
+    /// it stays in `Dialing` state, i.e. until `mark_connected` or
+    /// `mark_dial_failed`/`mark_disconnected` settles it — not just for the
+    /// duration of this call — so the cap bounds dials actually in flight.
+    pub fn due_for_redial(&mut self) -> Vec<PeerId> {
+        let in_flight = self.peers.values().filter(|e| e.state == PeerState::Dialing).count();
+        if in_flight >= MAX_CONCURRENT_DIALS {
+            return Vec::new();
+        }
+        let now = Instant::now();
+        let budget = MAX_CONCURRENT_DIALS - in_flight;
+
+        let mut due = Vec::new();
+        for (peer_id, entry) in self.peers.iter_mut() {
+            if due.len() >= budget {
+                break;
+            }
+            if let PeerState::BackingOff { next_retry } = entry.state {
+                if next_retry <= now {
+                    entry.state = PeerState::Dialing;
+                    due.push(*peer_id);
+                }
+            }
+        }
+        due
+    }
+
+    pub fn snapshot(&self) -> Vec<(PeerId, PeerState)> {
+        self.peers.iter().map(|(id, entry)| (*id, entry.state.clone())).collect()
+    }
+}
@@ -0,0 +1,117 @@
+// Message protocol: length-prefixed request/response frames over a dedicated
+// libp2p stream protocol. Framing is `[u32 len][u16 req_id][payload bytes]`,
+// where `len` covers the req_id plus payload. The req_id is assigned by the
+// sender (an AtomicU16 counter) and echoed back in the response frame so the
+// original `send` future can be resolved out of a pending-request map.
+
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::request_response;
+use libp2p::StreamProtocol;
+use std::io;
+use std::sync::atomic::{AtomicU16, Ordering};
+
+pub const PROTOCOL_NAME: StreamProtocol = StreamProtocol::new("/p2p-simple/msg/0.1.0");
+
+/// Upper bound on a single frame's declared length, so a peer can't make us
+/// allocate an arbitrary amount of memory via a forged length prefix.
+const MAX_FRAME_LEN: usize = 1 << 20;
+
+/// Wire-level request: a req_id plus the application payload.
+#[derive(Debug, Clone)]
+pub struct MsgRequest {
+    pub req_id: u16,
+    pub payload: Vec<u8>,
+}
+
+/// Wire-level response: echoes the req_id that produced it.
+#[derive(Debug, Clone)]
+pub struct MsgResponse {
+    pub req_id: u16,
+    pub payload: Vec<u8>,
+}
+
+/// Assigns outbound req_ids. Wraps on overflow, which is fine: ids only need
+/// to be unique among requests still awaiting a response.
+#[derive(Debug, Default)]
+pub struct ReqIdCounter(AtomicU16);
+
+impl ReqIdCounter {
+    pub fn next(&self) -> u16 {
+        self.0.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MsgCodec;
+
+async fn read_frame<T>(io: &mut T) -> io::Result<(u16, Vec<u8>)>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let mut len_buf = [0u8; 4];
+    io.read_exact(&mut len_buf).await?;
+    let len = u32::from_be_bytes(len_buf) as usize;
+    if len < 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame shorter than req_id"));
+    }
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame exceeds MAX_FRAME_LEN"));
+    }
+
+    let mut rest = vec![0u8; len];
+    io.read_exact(&mut rest).await?;
+
+    let req_id = u16::from_be_bytes([rest[0], rest[1]]);
+    let payload = rest.split_off(2);
+    Ok((req_id, payload))
+}
+
+async fn write_frame<T>(io: &mut T, req_id: u16, payload: &[u8]) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+{
+    let len = 2 + payload.len();
+    io.write_all(&(len as u32).to_be_bytes()).await?;
+    io.write_all(&req_id.to_be_bytes()).await?;
+    io.write_all(payload).await?;
+    io.flush().await?;
+    Ok(())
+}
+
+#[async_trait]
+impl request_response::Codec for MsgCodec {
+    type Protocol = StreamProtocol;
+    type Request = MsgRequest;
+    type Response = MsgResponse;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let (req_id, payload) = read_frame(io).await?;
+        Ok(MsgRequest { req_id, payload })
+    }
+
+    async fn read_response<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let (req_id, payload) = read_frame(io).await?;
+        Ok(MsgResponse { req_id, payload })
+    }
+
+    async fn write_request<T>(&mut self, _: &Self::Protocol, io: &mut T, req: Self::Request) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_frame(io, req.req_id, &req.payload).await
+    }
+
+    async fn write_response<T>(&mut self, _: &Self::Protocol, io: &mut T, res: Self::Response) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_frame(io, res.req_id, &res.payload).await
+    }
+}